@@ -0,0 +1,155 @@
+//! Apple target-triple classification and version parsing.
+//!
+//! This lives in its own crate, separate from `mlx-sys`, so it has no
+//! `build.rs`/`links` of its own: `cargo test -p mlx-apple-target` compiles
+//! and runs in milliseconds, without requiring mlx-c's CMake/Xcode build to
+//! succeed first. `mlx-sys/build.rs` depends on it as a path
+//! build-dependency and uses it to drive the CMake configure.
+
+/// Which Apple platform a Cargo `TARGET` triple is building for.
+///
+/// The `simulator` flag distinguishes the `-sim` triple suffix (e.g.
+/// `aarch64-apple-ios-sim`) from the on-device build.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ApplePlatform {
+    MacOs,
+    MacCatalyst,
+    Ios { simulator: bool },
+    Tvos { simulator: bool },
+    Watchos { simulator: bool },
+    Visionos { simulator: bool },
+}
+
+/// Classify a Cargo target triple into an `ApplePlatform`.
+pub fn classify_apple_platform(target: &str) -> ApplePlatform {
+    let simulator = target.contains("-sim");
+    if target.contains("macabi") {
+        ApplePlatform::MacCatalyst
+    } else if target.contains("-tvos") {
+        ApplePlatform::Tvos { simulator }
+    } else if target.contains("-watchos") {
+        ApplePlatform::Watchos { simulator }
+    } else if target.contains("-visionos") {
+        ApplePlatform::Visionos { simulator }
+    } else if target.contains("-ios") {
+        ApplePlatform::Ios { simulator }
+    } else {
+        ApplePlatform::MacOs
+    }
+}
+
+/// The `CMAKE_OSX_ARCHITECTURES` value implied by a Cargo target triple's
+/// architecture component.
+pub fn architectures_for_triple(target: &str) -> String {
+    match target.split('-').next() {
+        Some("aarch64") => "arm64".to_string(),
+        Some(other) => other.to_string(),
+        None => "arm64".to_string(),
+    }
+}
+
+/// Parse a dotted version string ("14.0", "10.13") into comparable
+/// components.
+pub fn parse_version(v: &str) -> Vec<u32> {
+    v.split('.').map(|p| p.parse().unwrap_or(0)).collect()
+}
+
+/// Compare two dotted version strings component-by-component, treating a
+/// missing trailing component as 0 (so "14" == "14.0").
+pub fn version_less_than(a: &str, b: &str) -> bool {
+    let a = parse_version(a);
+    let b = parse_version(b);
+    let len = a.len().max(b.len());
+    for i in 0..len {
+        let (x, y) = (
+            a.get(i).copied().unwrap_or(0),
+            b.get(i).copied().unwrap_or(0),
+        );
+        if x != y {
+            return x < y;
+        }
+    }
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn classifies_macos() {
+        assert_eq!(
+            classify_apple_platform("aarch64-apple-darwin"),
+            ApplePlatform::MacOs
+        );
+    }
+
+    #[test]
+    fn classifies_mac_catalyst() {
+        assert_eq!(
+            classify_apple_platform("aarch64-apple-ios-macabi"),
+            ApplePlatform::MacCatalyst
+        );
+    }
+
+    #[test]
+    fn classifies_ios_device_and_simulator() {
+        assert_eq!(
+            classify_apple_platform("aarch64-apple-ios"),
+            ApplePlatform::Ios { simulator: false }
+        );
+        assert_eq!(
+            classify_apple_platform("aarch64-apple-ios-sim"),
+            ApplePlatform::Ios { simulator: true }
+        );
+    }
+
+    #[test]
+    fn classifies_tvos_watchos_visionos() {
+        assert_eq!(
+            classify_apple_platform("aarch64-apple-tvos"),
+            ApplePlatform::Tvos { simulator: false }
+        );
+        assert_eq!(
+            classify_apple_platform("x86_64-apple-watchos-sim"),
+            ApplePlatform::Watchos { simulator: true }
+        );
+        assert_eq!(
+            classify_apple_platform("aarch64-apple-visionos"),
+            ApplePlatform::Visionos { simulator: false }
+        );
+    }
+
+    #[test]
+    fn architectures_map_aarch64_to_arm64() {
+        assert_eq!(architectures_for_triple("aarch64-apple-darwin"), "arm64");
+    }
+
+    #[test]
+    fn architectures_pass_through_other_arches() {
+        assert_eq!(architectures_for_triple("x86_64-apple-darwin"), "x86_64");
+    }
+
+    #[test]
+    fn parses_version_components() {
+        assert_eq!(parse_version("14.0"), vec![14, 0]);
+        assert_eq!(parse_version("10.13"), vec![10, 13]);
+        assert_eq!(parse_version("14"), vec![14]);
+    }
+
+    #[test]
+    fn version_less_than_handles_missing_trailing_components() {
+        // "14" == "14.0": a missing trailing component reads as 0, not less.
+        assert!(!version_less_than("14", "14.0"));
+        assert!(!version_less_than("14.0", "14"));
+    }
+
+    #[test]
+    fn version_less_than_compares_componentwise() {
+        assert!(version_less_than("9.0", "10.0"));
+        assert!(!version_less_than("10.0", "9.0"));
+        assert!(version_less_than("14.0", "14.1"));
+        assert!(!version_less_than("14.1", "14.0"));
+        assert!(!version_less_than("14.0", "14.0"));
+    }
+}
@@ -44,13 +44,185 @@ fn find_clang_rt_path() -> Option<String> {
     None
 }
 
-/// Resolve the macOS deployment target.
+/// Apple target-triple classification and version parsing live in the
+/// sibling `mlx-apple-target` crate (a path build-dependency with no
+/// `build.rs`/`links` of its own), so that pure logic is exercised by a
+/// plain `cargo test -p mlx-apple-target` — no Xcode/CMake/mlx-c build
+/// required — instead of only living in a `#[cfg(test)]` block that Cargo
+/// never builds for `build.rs` itself.
+#[cfg(target_os = "macos")]
+use mlx_apple_target::{
+    architectures_for_triple, classify_apple_platform, version_less_than, ApplePlatform,
+};
+
+/// The `xcrun --sdk <name>` identifier for `platform`.
+#[cfg(target_os = "macos")]
+fn sdk_name(platform: ApplePlatform) -> &'static str {
+    match platform {
+        ApplePlatform::MacOs => "macosx",
+        // Catalyst apps link against the macOS SDK, not an iOS one.
+        ApplePlatform::MacCatalyst => "macosx",
+        ApplePlatform::Ios { simulator: false } => "iphoneos",
+        ApplePlatform::Ios { simulator: true } => "iphonesimulator",
+        ApplePlatform::Tvos { simulator: false } => "appletvos",
+        ApplePlatform::Tvos { simulator: true } => "appletvsimulator",
+        ApplePlatform::Watchos { simulator: false } => "watchos",
+        ApplePlatform::Watchos { simulator: true } => "watchsimulator",
+        ApplePlatform::Visionos { simulator: false } => "xros",
+        ApplePlatform::Visionos { simulator: true } => "xrsimulator",
+    }
+}
+
+/// Env var consulted for `platform`'s deployment-target override.
+#[cfg(target_os = "macos")]
+fn deployment_target_var(platform: ApplePlatform) -> &'static str {
+    match platform {
+        ApplePlatform::MacOs => "MACOSX_DEPLOYMENT_TARGET",
+        // Catalyst binaries carry a Catalyst platform load command whose
+        // version is in iOS terms, so their floor is the iOS deployment
+        // target, not the macOS one.
+        ApplePlatform::MacCatalyst => "IPHONEOS_DEPLOYMENT_TARGET",
+        ApplePlatform::Ios { .. } => "IPHONEOS_DEPLOYMENT_TARGET",
+        ApplePlatform::Tvos { .. } => "TVOS_DEPLOYMENT_TARGET",
+        ApplePlatform::Watchos { .. } => "WATCHOS_DEPLOYMENT_TARGET",
+        ApplePlatform::Visionos { .. } => "XROS_DEPLOYMENT_TARGET",
+    }
+}
+
+/// Sensible minimum deployment target when the env var above isn't set.
+#[cfg(target_os = "macos")]
+fn default_deployment_target(platform: ApplePlatform) -> &'static str {
+    match platform {
+        // MLX requires macOS >= 14.0 for Metal support.
+        ApplePlatform::MacOs => "14.0",
+        ApplePlatform::MacCatalyst => "14.0",
+        ApplePlatform::Ios { .. } => "14.0",
+        ApplePlatform::Tvos { .. } => "14.0",
+        ApplePlatform::Watchos { .. } => "10.0",
+        ApplePlatform::Visionos { .. } => "1.0",
+    }
+}
+
+/// Classify the Cargo `TARGET` triple into an `ApplePlatform`.
+#[cfg(target_os = "macos")]
+fn apple_os() -> ApplePlatform {
+    classify_apple_platform(&env::var("TARGET").unwrap_or_default())
+}
+
+/// Resolve the deployment target for `platform`.
+///
+/// Reads the platform's deployment-target env var (e.g.
+/// `IPHONEOS_DEPLOYMENT_TARGET` for iOS) if set, otherwise falls back to
+/// `platform`'s sensible minimum.
+#[cfg(target_os = "macos")]
+fn resolve_deployment_target(platform: ApplePlatform) -> String {
+    env::var(deployment_target_var(platform))
+        .unwrap_or_else(|_| default_deployment_target(platform).to_string())
+}
+
+/// Ask `xcrun` for the SDK path matching `sdk_name` (e.g. `iphoneos`,
+/// `iphonesimulator`, `macosx`).
+#[cfg(target_os = "macos")]
+fn sdk_path(sdk_name: &str) -> Option<String> {
+    let output = Command::new("xcrun")
+        .args(["--sdk", sdk_name, "--show-sdk-path"])
+        .output()
+        .ok()?;
+
+    if !output.status.success() {
+        return None;
+    }
+
+    Some(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// The `CMAKE_OSX_ARCHITECTURES` value implied by the Cargo `TARGET` triple's
+/// architecture component.
+#[cfg(target_os = "macos")]
+fn cmake_osx_architectures() -> String {
+    architectures_for_triple(&env::var("TARGET").unwrap_or_default())
+}
+
+/// The minimum OS version libc++ itself supports on `platform`, independent
+/// of whatever floor MLX or the user requested. Mirrors the baseline cc-rs
+/// applies so we don't silently produce objects the C++ runtime can't load.
+#[cfg(target_os = "macos")]
+fn libcxx_baseline(platform: ApplePlatform) -> &'static str {
+    match platform {
+        // MLX's own Metal floor (14.0) is only the default that
+        // `default_deployment_target` supplies when MACOSX_DEPLOYMENT_TARGET
+        // is unset; this is libc++'s own historical floor, so an explicit
+        // lower macOS target is raised only as far as 10.9.
+        ApplePlatform::MacOs => "10.9",
+        ApplePlatform::MacCatalyst => "7.0",
+        ApplePlatform::Ios { .. } => "7.0",
+        ApplePlatform::Tvos { .. } => "9.0",
+        ApplePlatform::Watchos { .. } => "1.0",
+        ApplePlatform::Visionos { .. } => "1.0",
+    }
+}
+
+/// Raise `target` to `platform`'s libc++ baseline if it falls below it,
+/// warning the user when that happens.
+#[cfg(target_os = "macos")]
+fn apply_libcxx_floor(platform: ApplePlatform, target: String) -> String {
+    let baseline = libcxx_baseline(platform);
+    if version_less_than(&target, baseline) {
+        println!(
+            "cargo:warning=mlx-sys: requested {} deployment target {} is below the libc++ baseline {}; raising it",
+            sdk_name(platform),
+            target,
+            baseline
+        );
+        baseline.to_string()
+    } else {
+        target
+    }
+}
+
+/// The LLVM target triple clang must be passed so its bitcode modules merge
+/// with rustc's under cross-language ThinLTO.
 ///
-/// Uses `MACOSX_DEPLOYMENT_TARGET` env var if set, otherwise defaults to 14.0
-/// (MLX's minimum supported version for Metal).
+/// This mirrors the triple clang itself derives for `-target`: arch, vendor,
+/// OS, and — critically — the same deployment-version suffix resolved for
+/// the CMake configure, since mismatched versions make the LLVM modules
+/// unmergeable.
+#[cfg(all(target_os = "macos", feature = "lto"))]
+fn llvm_target_triple(platform: ApplePlatform, deployment_target: &str) -> String {
+    let arch = cmake_osx_architectures();
+    let os = match platform {
+        ApplePlatform::MacOs => format!("macosx{}", deployment_target),
+        ApplePlatform::MacCatalyst => format!("ios{}-macabi", deployment_target),
+        ApplePlatform::Ios { simulator: false } => format!("ios{}", deployment_target),
+        ApplePlatform::Ios { simulator: true } => format!("ios{}-simulator", deployment_target),
+        ApplePlatform::Tvos { simulator: false } => format!("tvos{}", deployment_target),
+        ApplePlatform::Tvos { simulator: true } => format!("tvos{}-simulator", deployment_target),
+        ApplePlatform::Watchos { simulator: false } => format!("watchos{}", deployment_target),
+        ApplePlatform::Watchos { simulator: true } => {
+            format!("watchos{}-simulator", deployment_target)
+        }
+        ApplePlatform::Visionos { simulator: false } => format!("xros{}", deployment_target),
+        ApplePlatform::Visionos { simulator: true } => {
+            format!("xros{}-simulator", deployment_target)
+        }
+    };
+    format!("{}-apple-{}", arch, os)
+}
+
+/// Tell cargo to rerun the build script when anything that feeds the CMake
+/// configure decided by `platform` changes.
+///
+/// This must run before `config.build()` so the env delta is recorded even
+/// when the cmake crate's own build-directory cache would otherwise skip a
+/// reconfigure, avoiding stale `libmlx.a` linked against the wrong minimum
+/// OS version.
 #[cfg(target_os = "macos")]
-fn resolve_deployment_target() -> String {
-    env::var("MACOSX_DEPLOYMENT_TARGET").unwrap_or_else(|_| "14.0".to_string())
+fn emit_rerun_triggers(platform: ApplePlatform) {
+    println!(
+        "cargo:rerun-if-env-changed={}",
+        deployment_target_var(platform)
+    );
+    println!("cargo:rerun-if-env-changed=SDKROOT");
 }
 
 /// Copy src/mlx-c to a staging directory and inject the metallib search-path
@@ -121,15 +293,21 @@ fn copy_dir_recursive(src: &std::path::Path, dst: &std::path::Path) -> std::io::
 }
 
 fn build_and_link_mlx_c() {
-    // MLX requires macOS >= 14.0 for Metal support. Override the deployment
-    // target early so the cmake crate (and cc crate) don't inject a lower
-    // -mmacosx-version-min flag into CFLAGS/CXXFLAGS. Without this, Cargo's
-    // default target (10.13) causes MLX's CMakeLists.txt to reject the build.
+    // Override the deployment target early so the cmake crate (and cc crate)
+    // don't inject a lower -mmacosx-version-min flag into CFLAGS/CXXFLAGS.
+    // Without this, Cargo's default target (10.13) causes MLX's
+    // CMakeLists.txt to reject the build.
     #[cfg(target_os = "macos")]
-    {
-        let target = resolve_deployment_target();
-        env::set_var("MACOSX_DEPLOYMENT_TARGET", &target);
-    }
+    let platform = apple_os();
+
+    #[cfg(target_os = "macos")]
+    emit_rerun_triggers(platform);
+
+    #[cfg(target_os = "macos")]
+    let target = apply_libcxx_floor(platform, resolve_deployment_target(platform));
+
+    #[cfg(target_os = "macos")]
+    env::set_var(deployment_target_var(platform), &target);
 
     let mlx_c_src = prepare_mlx_c_source();
     let mut config = Config::new(&mlx_c_src);
@@ -138,8 +316,44 @@ fn build_and_link_mlx_c() {
 
     #[cfg(target_os = "macos")]
     {
-        let target = resolve_deployment_target();
-        config.define("CMAKE_OSX_DEPLOYMENT_TARGET", &target);
+        // CMAKE_OSX_DEPLOYMENT_TARGET is interpreted relative to
+        // CMAKE_OSX_SYSROOT, i.e. in macOS-version space. For Mac Catalyst
+        // `target` is an iOS-space version (e.g. "14.0" from
+        // IPHONEOS_DEPLOYMENT_TARGET) read against the "macosx" SDK, so
+        // defining it here would hand CMake's Darwin platform module a
+        // bogus `-mmacosx-version-min=14.0` floor. The explicit
+        // `-target ...-macabi` flag below is the sole source of the
+        // Catalyst deployment version.
+        if platform != ApplePlatform::MacCatalyst {
+            config.define("CMAKE_OSX_DEPLOYMENT_TARGET", &target);
+        }
+        config.define("CMAKE_OSX_ARCHITECTURES", cmake_osx_architectures());
+        if let Some(sysroot) = sdk_path(sdk_name(platform)) {
+            config.define("CMAKE_OSX_SYSROOT", &sysroot);
+        }
+    }
+
+    // Mac Catalyst builds against the macOS SDK above, but still needs the
+    // Catalyst `-target <arch>-apple-ios<ver>-macabi` flag so the resulting
+    // objects carry the Catalyst `LC_BUILD_VERSION` platform instead of a
+    // bare macOS one.
+    #[cfg(target_os = "macos")]
+    if platform == ApplePlatform::MacCatalyst {
+        let catalyst_target = format!("{}-apple-ios{}-macabi", cmake_osx_architectures(), target);
+        config.cflag(format!("-target {}", catalyst_target));
+        config.cxxflag(format!("-target {}", catalyst_target));
+    }
+
+    // Cross-language ThinLTO between mlx-c's C++ objects and rustc: compile
+    // mlx-c as bitcode-bearing archives targeting the exact triple rustc
+    // emits, so the LLVM modules are mergeable at the Rust link step.
+    #[cfg(all(target_os = "macos", feature = "lto"))]
+    {
+        let triple = llvm_target_triple(platform, &target);
+        config.define("CMAKE_C_COMPILER_TARGET", &triple);
+        config.define("CMAKE_CXX_COMPILER_TARGET", &triple);
+        config.cflag("-flto=thin");
+        config.cxxflag("-flto=thin");
     }
 
     // Use Xcode's clang to ensure compatibility with the macOS SDK
@@ -180,6 +394,33 @@ fn build_and_link_mlx_c() {
     println!("cargo:rustc-link-lib=dylib=objc");
     println!("cargo:rustc-link-lib=framework=Foundation");
 
+    // Merging mlx-c's bitcode archives with rustc's own ThinLTO modules needs
+    // `-fuse-ld=lld -flto=thin` on the *downstream* binary's link line (lld is
+    // required because ld64/ld-classic can't consume mixed-origin LLVM
+    // bitcode). mlx-sys is a `links = "mlx"` sys crate with no bin/test/
+    // example/cdylib targets of its own, so `cargo:rustc-link-arg-bins` (and
+    // the `-tests`/`-examples`/cdylib variants) are validated against *this
+    // package's own* target list: cargo hard-errors with "does not have a bin
+    // target" the moment they're printed here, and even when a package does
+    // have matching targets the flags only apply to that package's own
+    // targets, never to a separate downstream consumer.
+    //
+    // Export the flags as `links` metadata instead: any `cargo:KEY=VALUE`
+    // instruction printed here reaches a crate that depends on mlx-sys as
+    // `DEP_MLX_KEY` in *that* crate's own build script. The root crate that
+    // actually produces a binary should read `DEP_MLX_LTO_LINK_ARGS` there and
+    // re-emit each word as `cargo:rustc-link-arg`:
+    //
+    //   if let Ok(args) = std::env::var("DEP_MLX_LTO_LINK_ARGS") {
+    //       for arg in args.split_whitespace() {
+    //           println!("cargo:rustc-link-arg={arg}");
+    //       }
+    //   }
+    #[cfg(all(target_os = "macos", feature = "lto"))]
+    {
+        println!("cargo:lto_link_args=-fuse-ld=lld -flto=thin");
+    }
+
     #[cfg(feature = "metal")]
     {
         println!("cargo:rustc-link-lib=framework=Metal");
@@ -0,0 +1,6 @@
+//! Raw FFI bindings to `mlx-c`, generated by `bindgen` in `build.rs`.
+#![allow(non_upper_case_globals)]
+#![allow(non_camel_case_types)]
+#![allow(non_snake_case)]
+
+include!(concat!(env!("OUT_DIR"), "/bindings.rs"));